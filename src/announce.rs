@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use chrono::{
+	Local,
+	NaiveDate,
+};
+use cron::Schedule;
+use miette::{
+	IntoDiagnostic,
+	Result,
+	WrapErr,
+};
+use serenity::{
+	model::id::{
+		ChannelId,
+		MessageId,
+	},
+	prelude::{
+		Context,
+		TypeMapKey,
+	},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{
+	debug,
+	info,
+	warn,
+};
+
+use crate::{
+	commands::build_embed,
+	MensaApiKey,
+};
+
+/// Remembers the announcement message for a given date and canteen, so a
+/// later tick can edit it in place instead of posting a duplicate when the
+/// menu changes.
+pub struct AnnounceMessagesKey;
+impl TypeMapKey for AnnounceMessagesKey {
+	type Value = HashMap<(NaiveDate, String), MessageId>;
+}
+
+/// Spawns the background worker that keeps the configured announcement
+/// channel up to date with the current day's menu.
+pub async fn register(ctx: &Context, cancel: CancellationToken, channel: ChannelId, schedule: Schedule) -> Result<()> {
+	let ctx = ctx.clone();
+	tokio::spawn(async move {
+		loop {
+			let next = match schedule.upcoming(Local).next() {
+				Some(next) => next,
+				None => {
+					warn!("Announce cron schedule has no upcoming occurrences, stopping announcer.");
+					return;
+				},
+			};
+
+			let duration = (next - Local::now()).to_std().unwrap_or_default();
+
+			tokio::select! {
+				_ = cancel.cancelled() => {
+					info!("Announcer cancelled.");
+					return;
+				},
+				_ = tokio::time::sleep(duration) => {},
+			}
+
+			if let Err(e) = announce_once(&ctx, channel).await {
+				warn!("Failed to post scheduled announcement: {:?}", e);
+			}
+		}
+	});
+
+	Ok(())
+}
+
+async fn announce_once(ctx: &Context, channel: ChannelId) -> Result<()> {
+	let today = Local::now().naive_local().date();
+
+	let data = ctx.data.read().await;
+	let api = data.get::<MensaApiKey>().unwrap();
+	// force a fresh fetch, the menu may have changed since the last tick
+	api.clear_cache().await;
+	let menu = api.get_canteen_data(&today).await?;
+	drop(data);
+
+	// mirrors the `mensa` command: announce every canteen that has a plan for the day, not just the first
+	let open_canteens: Vec<_> = menu.iter().filter(|c| c.lines.iter().any(|l| !l.meals.is_empty())).collect();
+
+	if open_canteens.is_empty() {
+		debug!("No canteen data available for {}, skipping scheduled announcement.", today);
+		return Ok(());
+	}
+
+	let mut data = ctx.data.write().await;
+	let messages = data.entry::<AnnounceMessagesKey>().or_insert_with(HashMap::new);
+
+	for canteen in open_canteens {
+		let key = (today, canteen.canteen.id.clone());
+
+		if let Some(message_id) = messages.get(&key).copied() {
+			channel
+				.edit_message(&ctx.http, message_id, |m| m.embed(|e| build_embed(e, canteen, |_| true)))
+				.await
+				.into_diagnostic()
+				.wrap_err("Failed to edit existing announcement message.")?;
+		} else {
+			let message = channel
+				.send_message(&ctx.http, |m| m.embed(|e| build_embed(e, canteen, |_| true)))
+				.await
+				.into_diagnostic()
+				.wrap_err("Failed to send announcement message.")?;
+			messages.insert(key, message.id);
+		}
+	}
+
+	Ok(())
+}
@@ -10,7 +10,10 @@ use miette::{
 	WrapErr,
 };
 use serenity::{
-	builder::CreateEmbed,
+	builder::{
+		CreateEmbed,
+		CreateInteractionResponseData,
+	},
 	model::{
 		application::interaction::application_command::ApplicationCommandInteraction,
 		prelude::{
@@ -34,11 +37,13 @@ use tracing::{
 };
 
 use crate::{
+	format::format_line,
 	mensa::api::{
 		CanteenData,
 		Classifier,
-		Line,
+		Meal,
 	},
+	prefs::PrefsStoreKey,
 	MensaApiKey,
 	TurnOverKey,
 };
@@ -81,17 +86,9 @@ async fn register_slash_commands(ctx: &Context) -> Result<()> {
 				.kind(CommandType::ChatInput)
 				.create_option(|o| {
 					o.name("tag")
-						.description("Tag für den der Speiseplan angezeigt werden soll.")
+						.description("z.B. \"heute\", \"übermorgen\", \"montag\", \"in 3 tagen\" oder \"24.12.\"")
 						.kind(CommandOptionType::String)
 						.required(false)
-						.add_string_choice("Heute", "today")
-						.add_string_choice("Morgen", "tomorrow")
-						.add_string_choice("Übermorgen", "dayaftertomorrow")
-						.add_string_choice("Montag", "monday")
-						.add_string_choice("Dienstag", "tuesday")
-						.add_string_choice("Mittwoch", "wednesday")
-						.add_string_choice("Donnerstag", "thursday")
-						.add_string_choice("Freitag", "friday")
 				})
 				.create_option(|o| {
 					o.name("kantine")
@@ -104,6 +101,45 @@ async fn register_slash_commands(ctx: &Context) -> Result<()> {
 					}
 					o
 				})
+				.create_option(|o| {
+					o.name("filter")
+						.description("Zeige nur Gerichte, die zu diesem Filter passen.")
+						.kind(CommandOptionType::String)
+						.required(false)
+						.add_string_choice("Vegan", "vegan")
+						.add_string_choice("Vegetarisch", "vegetarian")
+						.add_string_choice("Ohne Schwein", "no-pork")
+						.add_string_choice("Ohne Rind", "no-beef")
+						.add_string_choice("Fisch", "fish")
+						.add_string_choice("MensaVital", "mensa-vital")
+				})
+		})
+		.create_application_command(|c| {
+			c.name("mensa-prefs")
+				.description("Lege deine Standard-Kantine und Diätpräferenz fest.")
+				.dm_permission(true)
+				.kind(CommandType::ChatInput)
+				.create_option(|o| {
+					o.name("kantine")
+						.description("Standard-Kantine, die verwendet wird, wenn du keine angibst.")
+						.kind(CommandOptionType::String)
+						.required(false)
+						.add_string_choice("Keine (löschen)", "none");
+
+					for (name, value) in CANTEEN_LIST {
+						o.add_string_choice(name, value);
+					}
+					o
+				})
+				.create_option(|o| {
+					o.name("filter")
+						.description("Zeige nur Gerichte, die zu dieser Präferenz passen.")
+						.kind(CommandOptionType::String)
+						.required(false)
+						.add_string_choice("Keine (löschen)", "none")
+						.add_string_choice("Vegan", "vegan")
+						.add_string_choice("Vegetarisch", "vegetarian")
+				})
 		})
 	})
 	.await
@@ -126,6 +162,10 @@ pub async fn handle_application_command(ctx: Context, interaction: ApplicationCo
 			handle_mensa_command(ctx, interaction).await?;
 			Ok(())
 		},
+		"mensa-prefs" => {
+			handle_mensa_prefs_command(ctx, interaction).await?;
+			Ok(())
+		},
 		_ => Err(UnknownCommandError {
 			name: interaction.data.name,
 		})
@@ -133,7 +173,7 @@ pub async fn handle_application_command(ctx: Context, interaction: ApplicationCo
 	}
 }
 
-#[derive(PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum DayCorrection {
 	/// Request could be fully processed without any correction to the date.
 	Same,
@@ -145,40 +185,188 @@ enum DayCorrection {
 	DaysSkipped,
 }
 
-async fn handle_mensa_command(ctx: Context, interaction: ApplicationCommandInteraction) -> Result<()> {
-	let data = ctx.data.read().await;
-	let api = data.get::<MensaApiKey>().unwrap();
-	let roll_over_time = data.get::<TurnOverKey>().unwrap().0;
+/// A dietary/allergen filter narrowing down which meals are shown for the
+/// `filter` option of `/mensa`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MealFilter {
+	Vegan,
+	Vegetarian,
+	NoPork,
+	NoBeef,
+	Fish,
+	MensaVital,
+}
 
+impl MealFilter {
+	fn parse(value: &str) -> Option<Self> {
+		Some(match value {
+			"vegan" => MealFilter::Vegan,
+			"vegetarian" => MealFilter::Vegetarian,
+			"no-pork" => MealFilter::NoPork,
+			"no-beef" => MealFilter::NoBeef,
+			"fish" => MealFilter::Fish,
+			"mensa-vital" => MealFilter::MensaVital,
+			_ => return None,
+		})
+	}
+
+	fn matches(self, meal: &Meal) -> bool {
+		match self {
+			MealFilter::Vegan => meal.classifiers.contains(&Classifier::Vegan),
+			MealFilter::Vegetarian => meal.classifiers.iter().any(|c| matches!(c, Classifier::Vegetarian | Classifier::Vegan)),
+			MealFilter::NoPork => !meal.classifiers.iter().any(|c| matches!(c, Classifier::Pork | Classifier::OrganicPork)),
+			MealFilter::NoBeef => !meal.classifiers.iter().any(|c| matches!(c, Classifier::Beef | Classifier::OrganicBeef)),
+			MealFilter::Fish => meal.classifiers.contains(&Classifier::Fish),
+			MealFilter::MensaVital => meal.classifiers.contains(&Classifier::MensaVital),
+		}
+	}
+}
+
+/// The dietary/allergen filter actually applied to a `/mensa` invocation: an
+/// explicit `filter` argument always wins over the user's stored
+/// `/mensa-prefs` preference, it is never combined with it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ActiveFilter {
+	None,
+	Explicit(MealFilter),
+	Dietary(Classifier),
+}
+
+/// Pure resolution of which date to show for `/mensa` and why, given the
+/// current time, the configured roll-over time, the set of available plan
+/// dates, and the optional `tag` argument. Extracted out of
+/// [`handle_mensa_command`] so it is exercisable without a live Discord
+/// context or HTTP call. Returns `None` if no available plan matches.
+fn resolve_day(
+	now: chrono::NaiveDateTime,
+	roll_over: chrono::NaiveTime,
+	available_plans: &[chrono::NaiveDate],
+	arg: Option<&str>,
+) -> Result<Option<(chrono::NaiveDate, DayCorrection)>> {
 	let mut day_correction = DayCorrection::Same;
 
 	// if argument is given, parse it, otherwise use current date but add one day if it is past roll-over time
-	let lookup_date = interaction
-		.data
-		.options
-		.iter()
-		.find(|option| option.name == "tag")
-		.map(|option| {
-			let value = option.value.as_ref().unwrap().as_str().unwrap();
-			parse_day_argument(value)
-		})
-		.unwrap_or_else(|| {
-			// check if current time is past roll-over time, if so, add one day to the date
-			let mut lookup_date = chrono::Local::now().naive_local();
-			if lookup_date.time() > roll_over_time {
+	let lookup_date = match arg {
+		Some(value) => parse_day_argument_at(value, now.date())?,
+		None => {
+			let mut lookup_date = now.date();
+			if now.time() > roll_over {
 				lookup_date += chrono::Duration::days(1);
 				day_correction = DayCorrection::RollOver;
 			}
-			Ok(lookup_date.date())
-		})?;
+			lookup_date
+		},
+	};
 
 	// check if day matches available plans, if not, find following day
-	let mut available_plans = api.get_available_plans().await?;
+	let mut available_plans = available_plans.to_vec();
 	available_plans.sort();
-	let plan = available_plans.into_iter().find(|plan| plan >= &lookup_date);
+	let plan = match available_plans.into_iter().find(|plan| plan >= &lookup_date) {
+		Some(plan) => plan,
+		None => return Ok(None),
+	};
+
+	// if selected date does not match lookup date, we inform user that we skipped to the next available date
+	if plan != lookup_date {
+		day_correction = DayCorrection::DaysSkipped;
+	}
+
+	Ok(Some((plan, day_correction)))
+}
+
+async fn handle_mensa_command(ctx: Context, interaction: ApplicationCommandInteraction) -> Result<()> {
+	let data = ctx.data.read().await;
+	let api = data.get::<MensaApiKey>().unwrap();
+	let roll_over_time = data.get::<TurnOverKey>().unwrap().0;
+	let prefs_store = data.get::<PrefsStoreKey>().unwrap();
+	let user_prefs = prefs_store.get(interaction.user.id.0).await?;
+
+	let tag_arg = find_string_option(&interaction, "tag");
+	// fall back to the user's stored default canteen if none was given explicitly
+	let kantine_arg = find_string_option(&interaction, "kantine").or_else(|| user_prefs.canteen_id.clone());
+	// an explicit `filter` argument overrides the user's stored dietary preference, same as `kantine_arg` above
+	let filter_arg = find_string_option(&interaction, "filter").and_then(|value| MealFilter::parse(&value));
+	let active_filter = match filter_arg {
+		Some(filter) => ActiveFilter::Explicit(filter),
+		None => match user_prefs.dietary_filter {
+			Some(classifier) => ActiveFilter::Dietary(classifier),
+			None => ActiveFilter::None,
+		},
+	};
+	let meal_filter = move |meal: &Meal| match active_filter {
+		ActiveFilter::None => true,
+		ActiveFilter::Explicit(filter) => filter.matches(meal),
+		ActiveFilter::Dietary(classifier) => meal.classifiers.contains(&classifier),
+	};
+
+	let available_plans = api.get_available_plans().await?;
+	let resolved = resolve_day(chrono::Local::now().naive_local(), roll_over_time, &available_plans, tag_arg.as_deref());
+
+	// an unparseable `tag` argument is a user error, not a bug: report it back instead of
+	// bubbling it up to `handle_application_command`, which would just log a warning and leave
+	// Discord showing a generic "this interaction failed".
+	let resolved = match resolved {
+		Ok(resolved) => resolved,
+		Err(e) if e.downcast_ref::<InvalidDayArgumentError>().is_some() => {
+			interaction
+				.create_interaction_response(&ctx.http, |r| {
+					r.kind(ChannelMessageWithSource).interaction_response_data(|d| d.content(e.to_string()))
+				})
+				.await
+				.into_diagnostic()?;
+			return Ok(());
+		},
+		Err(e) => return Err(e),
+	};
 
 	// if no plan remains, we inform user that no plan is available
-	if plan.is_none() {
+	let (plans, day_correction) = match resolved {
+		Some(resolved) => resolved,
+		None => {
+			interaction
+				.create_interaction_response(&ctx.http, |r| {
+					r.kind(ChannelMessageWithSource).interaction_response_data(|d| d.content("No menu available."))
+				})
+				.await
+				.into_diagnostic()?;
+			return Ok(());
+		},
+	};
+
+	let menu = api.get_canteen_data(&plans).await?;
+
+	// a specific canteen was requested, show only that one (or tell the user it's closed)
+	if let Some(canteen_id) = &kantine_arg {
+		let canteen = menu.iter().find(|c| &c.canteen.id == canteen_id && c.lines.iter().any(|l| !l.meals.is_empty()));
+		return match canteen {
+			Some(canteen) => {
+				interaction
+					.create_interaction_response(&ctx.http, |r| {
+						r.kind(ChannelMessageWithSource).interaction_response_data(|d| {
+							apply_day_correction_message(d, &day_correction);
+							d.embed(|e| build_embed(e, canteen, meal_filter))
+						})
+					})
+					.await
+					.into_diagnostic()
+			},
+			None => {
+				let name = canteen_name(canteen_id);
+				interaction
+					.create_interaction_response(&ctx.http, |r| {
+						r.kind(ChannelMessageWithSource)
+							.interaction_response_data(|d| d.content(format!("Kein Speiseplan für {} am ausgewählten Tag verfügbar.", name)))
+					})
+					.await
+					.into_diagnostic()
+			},
+		};
+	}
+
+	// no canteen selected, show every canteen that has a plan for the day
+	let open_canteens: Vec<&CanteenData> = menu.iter().filter(|c| c.lines.iter().any(|l| !l.meals.is_empty())).collect();
+
+	if open_canteens.is_empty() {
 		interaction
 			.create_interaction_response(&ctx.http, |r| {
 				r.kind(ChannelMessageWithSource).interaction_response_data(|d| d.content("No menu available."))
@@ -187,32 +375,48 @@ async fn handle_mensa_command(ctx: Context, interaction: ApplicationCommandInter
 			.into_diagnostic()?;
 		return Ok(());
 	}
-	let plans = plan.unwrap();
 
-	// if selected date does not match lookup date, we inform user that we skipped to the next available date
-	if plans != lookup_date {
-		day_correction = DayCorrection::DaysSkipped;
+	interaction
+		.create_interaction_response(&ctx.http, |r| {
+			r.kind(ChannelMessageWithSource).interaction_response_data(|d| {
+				apply_day_correction_message(d, &day_correction);
+				for canteen in &open_canteens {
+					let mut embed = CreateEmbed::default();
+					build_embed(&mut embed, canteen, meal_filter);
+					d.add_embed(embed);
+				}
+				d
+			})
+		})
+		.await
+		.into_diagnostic()?;
+
+	Ok(())
+}
+
+async fn handle_mensa_prefs_command(ctx: Context, interaction: ApplicationCommandInteraction) -> Result<()> {
+	let data = ctx.data.read().await;
+	let prefs_store = data.get::<PrefsStoreKey>().unwrap();
+	let user_id = interaction.user.id.0;
+
+	if let Some(value) = find_string_option(&interaction, "kantine") {
+		let canteen_id = if value == "none" { None } else { Some(value.as_str()) };
+		prefs_store.set_canteen(user_id, canteen_id).await?;
 	}
 
-	let menu = api.get_canteen_data(&plans).await?;
-	// take first menu, as we only have one canteen TODO fix
-	let canteen = menu.get(0).unwrap();
+	if let Some(value) = find_string_option(&interaction, "filter") {
+		let classifier = match value.as_str() {
+			"none" => None,
+			"vegan" => Some(Classifier::Vegan),
+			"vegetarian" => Some(Classifier::Vegetarian),
+			_ => None,
+		};
+		prefs_store.set_dietary_filter(user_id, classifier).await?;
+	}
 
-	// print available menu
 	interaction
 		.create_interaction_response(&ctx.http, |r| {
-			r.kind(ChannelMessageWithSource).interaction_response_data(|d| {
-				match day_correction {
-					DayCorrection::RollOver => {
-						d.content("Die Mensa ist geschlossen. Ich habe dir den nächsten Tag ausgewählt.");
-					},
-					DayCorrection::DaysSkipped => {
-						d.content("An dem ausgewählten Tag ist die Mensa geschlossen. Ich habe dir den nächsten Tag ausgewählt.");
-					},
-					_ => {},
-				};
-				d.embed(|e| build_embed(e, canteen))
-			})
+			r.kind(ChannelMessageWithSource).interaction_response_data(|d| d.content("Deine Einstellungen wurden gespeichert."))
 		})
 		.await
 		.into_diagnostic()?;
@@ -220,37 +424,51 @@ async fn handle_mensa_command(ctx: Context, interaction: ApplicationCommandInter
 	Ok(())
 }
 
-fn build_embed<'a>(embed: &'a mut CreateEmbed, canteen: &CanteenData) -> &'a mut CreateEmbed {
+/// Reads a top-level string option from the command invocation by name.
+fn find_string_option(interaction: &ApplicationCommandInteraction, name: &str) -> Option<String> {
+	interaction.data.options.iter().find(|option| option.name == name).map(|option| option.value.as_ref().unwrap().as_str().unwrap().to_string())
+}
+
+/// Sets the interaction response content to explain why the shown day
+/// differs from what the user asked for, if at all.
+fn apply_day_correction_message(data: &mut CreateInteractionResponseData, correction: &DayCorrection) {
+	match correction {
+		DayCorrection::RollOver => {
+			data.content("Die Mensa ist geschlossen. Ich habe dir den nächsten Tag ausgewählt.");
+		},
+		DayCorrection::DaysSkipped => {
+			data.content("An dem ausgewählten Tag ist die Mensa geschlossen. Ich habe dir den nächsten Tag ausgewählt.");
+		},
+		_ => {},
+	}
+}
+
+/// Resolves a canteen id back to its display name, falling back to the id
+/// itself if it is unknown.
+fn canteen_name(canteen_id: &str) -> &str {
+	CANTEEN_LIST.iter().find(|(_, id)| *id == canteen_id).map(|(name, _)| *name).unwrap_or(canteen_id)
+}
+
+pub(crate) fn build_embed<'a>(embed: &'a mut CreateEmbed, canteen: &CanteenData, filter: impl Fn(&Meal) -> bool + Copy) -> &'a mut CreateEmbed {
 	embed
 		.title(format!("Mensaeinheitsbrei für {} am {}", canteen.canteen.name, weekday_to_string(canteen.date.weekday())))
 		.color(0x6f00ff)
 		.footer(|f| f.text("Klick auf mein Profilbild und lad mich zu deinem Server ein!"));
 
 	for line in &canteen.lines {
-		// skip empty lines
-		if line.meals.is_empty() {
+		let formatted = format_line(line, filter);
+
+		// skip lines with no matching meals
+		if formatted.is_empty() {
 			continue;
 		}
 
-		embed.field(line.name.as_str(), format_line(line), true);
+		embed.field(line.name.as_str(), formatted, true);
 	}
 
 	embed
 }
 
-fn format_line(line: &Line) -> String {
-	line
-		.meals
-		.iter()
-		.filter(|m| {
-			// filter out meals with empty price
-			!m.price.is_empty()
-		})
-		.map(|meal| format!("{}{} ({})", emojiy_classifier(&meal.classifiers), meal.name, meal.price))
-		.collect::<Vec<String>>()
-		.join("\n")
-}
-
 fn weekday_to_string(weekday: chrono::Weekday) -> &'static str {
 	match weekday {
 		chrono::Weekday::Mon => "Montag",
@@ -262,19 +480,32 @@ fn weekday_to_string(weekday: chrono::Weekday) -> &'static str {
 	}
 }
 
-fn parse_day_argument(arg: &str) -> Result<chrono::NaiveDate> {
-	let now = chrono::Local::now().naive_local();
-
-	// parse day argument
-	let date = match arg {
-		"today" => now.date(),
-		"tomorrow" => now.date() + chrono::Duration::days(1),
-		"dayaftertomorrow" => now.date() + chrono::Duration::days(2),
-		"monday" => next_weekday(chrono::Weekday::Mon),
-		"tuesday" => next_weekday(chrono::Weekday::Tue),
-		"wednesday" => next_weekday(chrono::Weekday::Wed),
-		"thursday" => next_weekday(chrono::Weekday::Thu),
-		"friday" => next_weekday(chrono::Weekday::Fri),
+/// Parses the free-text `tag` argument relative to today's date. Understands
+/// the keywords below (German and English), bare weekday names resolving to
+/// their next occurrence, relative phrases like "in 3 tagen"/"in 3 days",
+/// and explicit dates in `DD.MM`, `DD.MM.YYYY` or `YYYY-MM-DD` form.
+fn parse_day_argument_at(arg: &str, today: chrono::NaiveDate) -> Result<chrono::NaiveDate> {
+	let normalized = arg.trim().to_lowercase();
+
+	if let Some(date) = parse_explicit_date(&normalized, today) {
+		return Ok(date);
+	}
+
+	if let Some(date) = parse_relative_days(&normalized, today) {
+		return Ok(date);
+	}
+
+	let date = match normalized.as_str() {
+		"today" | "heute" => today,
+		"tomorrow" | "morgen" => today + chrono::Duration::days(1),
+		"dayaftertomorrow" | "übermorgen" | "uebermorgen" => today + chrono::Duration::days(2),
+		"monday" | "montag" => next_weekday_from(chrono::Weekday::Mon, today),
+		"tuesday" | "dienstag" => next_weekday_from(chrono::Weekday::Tue, today),
+		"wednesday" | "mittwoch" => next_weekday_from(chrono::Weekday::Wed, today),
+		"thursday" | "donnerstag" => next_weekday_from(chrono::Weekday::Thu, today),
+		"friday" | "freitag" => next_weekday_from(chrono::Weekday::Fri, today),
+		"saturday" | "samstag" => next_weekday_from(chrono::Weekday::Sat, today),
+		"sunday" | "sonntag" => next_weekday_from(chrono::Weekday::Sun, today),
 		_ => {
 			return Err(InvalidDayArgumentError {
 				arg: arg.to_string(),
@@ -286,34 +517,44 @@ fn parse_day_argument(arg: &str) -> Result<chrono::NaiveDate> {
 	Ok(date)
 }
 
-/// Returns the next `naive_date` that is the given `weekday`.
-/// If today is the given `weekday`, the current date is returned.
-fn next_weekday(weekday: chrono::Weekday) -> chrono::NaiveDate {
-	let now = chrono::Local::now().naive_local();
-	let today = now.date();
-	let days_to_add = (weekday.number_from_monday() + 7 - today.weekday().number_from_monday()) % 7;
-	today + chrono::Duration::days(days_to_add as i64)
+/// Parses phrases of the form "in N tagen"/"in N tage"/"in N days"/"in N
+/// day".
+fn parse_relative_days(arg: &str, today: chrono::NaiveDate) -> Option<chrono::NaiveDate> {
+	let rest = arg.strip_prefix("in ")?;
+	let count = rest.strip_suffix(" tagen").or_else(|| rest.strip_suffix(" tage")).or_else(|| rest.strip_suffix(" days")).or_else(|| rest.strip_suffix(" day"))?;
+
+	let days: i64 = count.trim().parse().ok()?;
+	Some(today + chrono::Duration::days(days))
 }
 
-fn emojiy_classifier(classifier: &[Classifier]) -> &'static str {
-	// map each classifier to emoji
-	// group classifiers by type (beef, pork, ...) to same emoji
-	let mut classifier = Vec::from(classifier);
-	classifier.sort();
-	classifier
-		.iter()
-		.map(|c| match c {
-			Classifier::Pork | Classifier::OrganicPork => "🐖",
-			Classifier::Beef | Classifier::OrganicBeef => "🐄",
-			Classifier::Gelatine => "🐈",
-			Classifier::Fish => "🐟",
-			Classifier::Vegetarian => "🥕",
-			Classifier::MensaVital => "🥦",
-			Classifier::Vegan => "🌱",
-			_ => "",
-		})
-		.next()
-		.unwrap_or("")
+/// Parses `YYYY-MM-DD`, `DD.MM.YYYY` and `DD.MM` (year defaults to the
+/// current one, rolling over to the next year if that date already passed).
+fn parse_explicit_date(arg: &str, today: chrono::NaiveDate) -> Option<chrono::NaiveDate> {
+	if let Ok(date) = chrono::NaiveDate::parse_from_str(arg, "%Y-%m-%d") {
+		return Some(date);
+	}
+
+	if let Ok(date) = chrono::NaiveDate::parse_from_str(arg, "%d.%m.%Y") {
+		return Some(date);
+	}
+
+	let with_current_year = format!("{}.{}", arg.trim_end_matches('.'), today.year());
+	if let Ok(date) = chrono::NaiveDate::parse_from_str(&with_current_year, "%d.%m.%Y") {
+		return Some(if date < today {
+			date.with_year(today.year() + 1).unwrap_or(date)
+		} else {
+			date
+		});
+	}
+
+	None
+}
+
+/// Returns the next `naive_date` relative to `today` that is the given
+/// `weekday`. If today is the given `weekday`, `today` itself is returned.
+fn next_weekday_from(weekday: chrono::Weekday, today: chrono::NaiveDate) -> chrono::NaiveDate {
+	let days_to_add = (weekday.number_from_monday() + 7 - today.weekday().number_from_monday()) % 7;
+	today + chrono::Duration::days(days_to_add as i64)
 }
 
 #[derive(Error, Diagnostic, Debug)]
@@ -323,7 +564,100 @@ pub struct UnknownCommandError {
 }
 
 #[derive(Error, Diagnostic, Debug)]
-#[error("InvalidDayArgument")]
+#[error(
+	"Konnte \"{arg}\" nicht als Tag verstehen. Erlaubt sind z.B. \"heute\", \"morgen\", \"übermorgen\", Wochentage, \"in 3 tagen\" \
+	 oder Daten wie \"24.12\", \"24.12.2026\" und \"2026-12-24\"."
+)]
 pub struct InvalidDayArgumentError {
 	arg: String,
 }
+
+#[cfg(test)]
+mod tests {
+	use chrono::{
+		NaiveDate,
+		NaiveDateTime,
+		NaiveTime,
+		Weekday,
+	};
+	use proptest::prelude::*;
+
+	use super::*;
+
+	fn arb_date() -> impl Strategy<Value = NaiveDate> {
+		(2020i32..2035, 1u32..=12, 1u32..=28).prop_map(|(y, m, d)| NaiveDate::from_ymd_opt(y, m, d).unwrap())
+	}
+
+	fn arb_time() -> impl Strategy<Value = NaiveTime> {
+		(0u32..24, 0u32..60, 0u32..60).prop_map(|(h, m, s)| NaiveTime::from_hms_opt(h, m, s).unwrap())
+	}
+
+	fn arb_weekday() -> impl Strategy<Value = Weekday> {
+		prop::sample::select(vec![
+			Weekday::Mon,
+			Weekday::Tue,
+			Weekday::Wed,
+			Weekday::Thu,
+			Weekday::Fri,
+			Weekday::Sat,
+			Weekday::Sun,
+		])
+	}
+
+	proptest! {
+		#[test]
+		fn next_weekday_from_is_the_given_weekday_within_a_week(today in arb_date(), weekday in arb_weekday()) {
+			let result = next_weekday_from(weekday, today);
+			prop_assert_eq!(result.weekday(), weekday);
+			prop_assert!(result >= today);
+			prop_assert!(result - today < chrono::Duration::days(7));
+		}
+
+		#[test]
+		fn resolved_plan_is_the_smallest_available_date_ge_lookup_date(
+			today in arb_date(),
+			time in arb_time(),
+			roll_over in arb_time(),
+			mut plans in prop::collection::vec(arb_date(), 0..10),
+		) {
+			plans.sort();
+			let now = NaiveDateTime::new(today, time);
+			let resolved = resolve_day(now, roll_over, &plans, None).unwrap();
+
+			let lookup_date = if time > roll_over { today + chrono::Duration::days(1) } else { today };
+			let expected = plans.iter().copied().filter(|plan| *plan >= lookup_date).min();
+
+			prop_assert_eq!(resolved.map(|(plan, _)| plan), expected);
+		}
+
+		#[test]
+		fn roll_over_is_reported_iff_past_roll_over_time_without_explicit_tag(
+			today in arb_date(),
+			time in arb_time(),
+			roll_over in arb_time(),
+		) {
+			let now = NaiveDateTime::new(today, time);
+			let lookup_date = if time > roll_over { today + chrono::Duration::days(1) } else { today };
+
+			// the rolled-over date itself is always open, so `DaysSkipped` can never mask `RollOver`
+			let plans = vec![lookup_date];
+			let (_, day_correction) = resolve_day(now, roll_over, &plans, None).unwrap().unwrap();
+
+			prop_assert_eq!(day_correction == DayCorrection::RollOver, time > roll_over);
+		}
+
+		#[test]
+		fn days_skipped_is_reported_iff_closed_on_the_lookup_day(
+			today in arb_date(),
+			mut plans in prop::collection::vec(arb_date(), 1..10),
+		) {
+			plans.sort();
+			let now = NaiveDateTime::new(today, NaiveTime::from_hms_opt(8, 0, 0).unwrap());
+			let roll_over = NaiveTime::from_hms_opt(23, 59, 59).unwrap();
+
+			if let Some((plan, day_correction)) = resolve_day(now, roll_over, &plans, None).unwrap() {
+				prop_assert_eq!(day_correction == DayCorrection::DaysSkipped, plan != today);
+			}
+		}
+	}
+}
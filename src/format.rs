@@ -0,0 +1,54 @@
+//! Meal/line formatting shared by every surface that renders a menu (the
+//! Discord embed in [`crate::commands`] and the iCalendar feed in
+//! [`crate::ical`]), so the two never drift apart.
+
+use crate::mensa::api::{
+	Classifier,
+	Line,
+	Meal,
+};
+
+/// Renders a line's meals, one per row. `filter` additionally narrows down
+/// which meals are shown, e.g. by dietary preference; pass `|_| true` to keep
+/// the previous "show everything" behaviour.
+pub fn format_line(line: &Line, filter: impl Fn(&Meal) -> bool) -> String {
+	line
+		.meals
+		.iter()
+		.filter(|m| {
+			// filter out meals with empty price
+			!m.price.is_empty()
+		})
+		.filter(|m| filter(m))
+		.map(|meal| {
+			let additives = if meal.additives.is_empty() {
+				String::new()
+			} else {
+				format!(" [{}]", meal.additives.join(","))
+			};
+			format!("{}{} ({}){}", emojiy_classifier(&meal.classifiers), meal.name, meal.price, additives)
+		})
+		.collect::<Vec<String>>()
+		.join("\n")
+}
+
+pub fn emojiy_classifier(classifier: &[Classifier]) -> &'static str {
+	// map each classifier to emoji
+	// group classifiers by type (beef, pork, ...) to same emoji
+	let mut classifier = Vec::from(classifier);
+	classifier.sort();
+	classifier
+		.iter()
+		.map(|c| match c {
+			Classifier::Pork | Classifier::OrganicPork => "🐖",
+			Classifier::Beef | Classifier::OrganicBeef => "🐄",
+			Classifier::Gelatine => "🐈",
+			Classifier::Fish => "🐟",
+			Classifier::Vegetarian => "🥕",
+			Classifier::MensaVital => "🥦",
+			Classifier::Vegan => "🌱",
+			_ => "",
+		})
+		.next()
+		.unwrap_or("")
+}
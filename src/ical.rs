@@ -0,0 +1,121 @@
+use std::{
+	net::SocketAddr,
+	sync::Arc,
+};
+
+use axum::{
+	extract::State,
+	http::{
+		header,
+		StatusCode,
+	},
+	response::{
+		IntoResponse,
+		Response,
+	},
+	routing::get,
+	Router,
+};
+use chrono::Duration;
+use miette::{
+	IntoDiagnostic,
+	Result,
+	WrapErr,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{
+	info,
+	warn,
+};
+
+use crate::{
+	format::format_line,
+	mensa::api::MensaApi,
+};
+
+/// Spawns the embedded HTTP server that serves the weekly menu as an
+/// iCalendar feed, so users can subscribe once and always see the upcoming
+/// menu without touching Discord.
+pub async fn register(bind: SocketAddr, api: Arc<MensaApi>, cancel: CancellationToken) -> Result<()> {
+	let app = Router::new().route("/calendar.ics", get(serve_calendar)).with_state(api);
+
+	let listener = tokio::net::TcpListener::bind(bind).await.into_diagnostic().wrap_err("Failed to bind iCal server.")?;
+
+	tokio::spawn(async move {
+		info!("Serving iCalendar feed on {}.", bind);
+
+		tokio::select! {
+			_ = cancel.cancelled() => {
+				info!("iCal server cancelled.");
+			},
+			result = axum::serve(listener, app) => {
+				if let Err(e) = result {
+					warn!("iCal server stopped with error: {}", e);
+				}
+			},
+		}
+	});
+
+	Ok(())
+}
+
+async fn serve_calendar(State(api): State<Arc<MensaApi>>) -> Response {
+	match build_calendar(&api).await {
+		Ok(body) => (StatusCode::OK, [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")], body).into_response(),
+		Err(e) => {
+			warn!("Failed to build iCal feed: {:?}", e);
+			StatusCode::INTERNAL_SERVER_ERROR.into_response()
+		},
+	}
+}
+
+async fn build_calendar(api: &MensaApi) -> Result<String> {
+	let mut plans = api.get_available_plans().await?;
+	plans.sort();
+
+	// RFC 5545 requires DTSTAMP on every VEVENT; stamp all of them with this feed generation time
+	let generated_at = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+
+	let mut calendar = String::new();
+	calendar.push_str("BEGIN:VCALENDAR\r\n");
+	calendar.push_str("VERSION:2.0\r\n");
+	calendar.push_str("PRODID:-//mensa-bot//mensa-bot//DE\r\n");
+	calendar.push_str("CALSCALE:GREGORIAN\r\n");
+
+	for date in plans {
+		let canteens = api.get_canteen_data(&date).await?;
+
+		for canteen in canteens {
+			// skip canteens without any menu for the day, nothing to show
+			if canteen.lines.iter().all(|line| line.meals.is_empty()) {
+				continue;
+			}
+
+			let description = canteen
+				.lines
+				.iter()
+				.filter(|line| !line.meals.is_empty())
+				.map(|line| format!("{}:\n{}", line.name, format_line(line, |_| true)))
+				.collect::<Vec<String>>()
+				.join("\n\n");
+
+			calendar.push_str("BEGIN:VEVENT\r\n");
+			calendar.push_str(&format!("UID:{}-{}@mensa-bot\r\n", canteen.canteen.id, date.format("%Y%m%d")));
+			calendar.push_str(&format!("DTSTAMP:{}\r\n", generated_at));
+			calendar.push_str(&format!("DTSTART;VALUE=DATE:{}\r\n", date.format("%Y%m%d")));
+			calendar.push_str(&format!("DTEND;VALUE=DATE:{}\r\n", (date + Duration::days(1)).format("%Y%m%d")));
+			calendar.push_str(&format!("SUMMARY:{}\r\n", escape_ical_text(&canteen.canteen.name)));
+			calendar.push_str(&format!("DESCRIPTION:{}\r\n", escape_ical_text(&description)));
+			calendar.push_str("END:VEVENT\r\n");
+		}
+	}
+
+	calendar.push_str("END:VCALENDAR\r\n");
+	Ok(calendar)
+}
+
+/// Escapes text per RFC 5545 section 3.3.11 so it is safe to embed in a
+/// `SUMMARY`/`DESCRIPTION` property value.
+fn escape_ical_text(text: &str) -> String {
+	text.replace('\\', "\\\\").replace(',', "\\,").replace(';', "\\;").replace('\n', "\\n")
+}
@@ -1,12 +1,21 @@
+mod announce;
 mod commands;
+mod format;
+mod ical;
 mod mensa;
+mod prefs;
 
 use std::{
+	net::SocketAddr,
 	str::FromStr,
-	sync::atomic::{
-		AtomicBool,
-		Ordering::SeqCst,
+	sync::{
+		atomic::{
+			AtomicBool,
+			Ordering::SeqCst,
+		},
+		Arc,
 	},
+	time::Duration,
 };
 
 use async_trait::async_trait;
@@ -26,6 +35,7 @@ use serenity::{
 	model::{
 		application::interaction::Interaction,
 		gateway::Ready,
+		id::ChannelId,
 	},
 	prelude::{
 		GatewayIntents,
@@ -40,7 +50,14 @@ use tracing::{
 	warn,
 };
 
-use crate::mensa::api::MensaApi;
+use crate::{
+	announce::AnnounceMessagesKey,
+	mensa::api::MensaApi,
+	prefs::{
+		PrefsStore,
+		PrefsStoreKey,
+	},
+};
 
 #[derive(Envconfig)]
 struct Config {
@@ -64,6 +81,37 @@ struct Config {
 	/// next day.
 	#[envconfig(from = "NEXT_DAY")]
 	pub next_day: TimeWrapper,
+
+	/// How long fetched menu data is cached before the upstream API is
+	/// queried again, in seconds.
+	#[envconfig(from = "CACHE_TTL", default = "300")]
+	pub cache_ttl: DurationWrapper,
+
+	/// Optional address to bind the iCalendar feed server to, e.g.
+	/// `0.0.0.0:8080`. If unset, no feed is served.
+	#[envconfig(from = "ICAL_BIND")]
+	pub ical_bind: Option<SocketAddr>,
+
+	/// Path to the SQLite database file used to persist per-user
+	/// preferences.
+	#[envconfig(from = "PREFS_DB_PATH", default = "mensa-bot-prefs.sqlite3")]
+	pub prefs_db_path: String,
+}
+
+struct DurationWrapper(Duration);
+
+impl FromStr for DurationWrapper {
+	type Err = std::num::ParseIntError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(Self(Duration::from_secs(s.parse()?)))
+	}
+}
+
+impl From<DurationWrapper> for Duration {
+	fn from(d: DurationWrapper) -> Self {
+		d.0
+	}
 }
 
 struct TimeWrapper(NaiveTime);
@@ -89,7 +137,7 @@ impl TypeMapKey for ApplicationCancelTokenKey {
 
 struct MensaApiKey;
 impl TypeMapKey for MensaApiKey {
-	type Value = MensaApi;
+	type Value = Arc<MensaApi>;
 }
 
 struct TurnOverKey;
@@ -97,6 +145,11 @@ impl TypeMapKey for TurnOverKey {
 	type Value = TimeWrapper;
 }
 
+struct AnnounceConfigKey;
+impl TypeMapKey for AnnounceConfigKey {
+	type Value = (ChannelId, Schedule);
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
 	tracing_subscriber::fmt::init();
@@ -114,11 +167,23 @@ async fn main() -> Result<()> {
 	let cancel_token = CancellationToken::new();
 	data.insert::<ApplicationCancelTokenKey>(cancel_token.clone());
 	let mensa_api_url = config.api_url.parse().into_diagnostic().wrap_err("Failed to parse API URL.")?;
-	let api = MensaApi::new(mensa_api_url);
-	data.insert::<MensaApiKey>(api);
+	let api = Arc::new(MensaApi::new(mensa_api_url, config.cache_ttl.into()));
+	data.insert::<MensaApiKey>(api.clone());
 	data.insert::<TurnOverKey>(config.next_day);
+	data.insert::<AnnounceMessagesKey>(Default::default());
+	let prefs_store = PrefsStore::open(&config.prefs_db_path).wrap_err("Failed to open preferences store.")?;
+	data.insert::<PrefsStoreKey>(prefs_store);
+
+	if let (Some(channel), Some(schedule)) = (config.announce_channel, config.announce_cron) {
+		let channel = channel.parse::<u64>().into_diagnostic().wrap_err("Failed to parse ANNOUNCE_CHANNEL as a channel id.")?.into();
+		data.insert::<AnnounceConfigKey>((channel, schedule));
+	}
 	drop(data);
 
+	if let Some(bind) = config.ical_bind {
+		ical::register(bind, api, cancel_token.clone()).await?;
+	}
+
 	{
 		let cancel_token = cancel_token.clone();
 		tokio::spawn(async move {
@@ -169,10 +234,21 @@ impl EventHandler for Handler {
 
 		if self.first_ready.compare_exchange(true, false, SeqCst, SeqCst).is_ok() {
 			let data = ctx.data.read().await;
-			let cancel_token = data.get::<ApplicationCancelTokenKey>().unwrap();
+			let cancel_token = data.get::<ApplicationCancelTokenKey>().unwrap().clone();
+			let announce_config = data.get::<AnnounceConfigKey>().cloned();
+			drop(data);
+
 			if let Err(e) = commands::register(&ctx, cancel_token.clone()).await {
 				warn!("Failed to register command logic: {}", e);
 			}
+
+			if let Some((channel, schedule)) = announce_config {
+				if let Err(e) = announce::register(&ctx, cancel_token, channel, schedule).await {
+					warn!("Failed to start announcer: {}", e);
+				}
+			} else {
+				info!("No ANNOUNCE_CHANNEL/ANNOUNCE_CRON configured, skipping announcer.");
+			}
 		}
 	}
 
@@ -1,3 +1,11 @@
+use std::{
+	collections::HashMap,
+	time::{
+		Duration,
+		Instant,
+	},
+};
+
 use chrono::NaiveDate;
 use miette::{
 	IntoDiagnostic,
@@ -9,6 +17,7 @@ use serde::{
 	Deserialize,
 	Deserializer,
 };
+use tokio::sync::RwLock;
 
 #[derive(Default, Debug, Clone, PartialEq, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -61,7 +70,7 @@ pub struct Meal {
 /// [MV] MensaVital
 /// [LAB] with animal rennet
 /// [GEL] with gelatine
-#[derive(Debug, Clone, PartialEq, PartialOrd, Ord, Eq, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Ord, Eq, Deserialize)]
 pub enum Classifier {
 	#[serde(rename = "S")]
 	Pork,
@@ -94,6 +103,42 @@ pub enum Classifier {
 	MensaVital,
 }
 
+impl Classifier {
+	/// The short code the upstream API uses for this classifier, also used to
+	/// persist and parse it outside of `serde` (e.g. in the SQLite store).
+	pub fn code(self) -> &'static str {
+		match self {
+			Classifier::Pork => "S",
+			Classifier::OrganicPork => "SAT",
+			Classifier::Beef => "R",
+			Classifier::OrganicBeef => "RAT",
+			Classifier::Gelatine => "GEL",
+			Classifier::Fish => "MSC",
+			Classifier::AnimalRennet => "LAB",
+			Classifier::Vegetarian => "VEG",
+			Classifier::Vegan => "VG",
+			Classifier::MensaVital => "MV",
+		}
+	}
+
+	/// Parses a classifier back from its short code, the inverse of [`Self::code`].
+	pub fn from_code(code: &str) -> Option<Self> {
+		Some(match code {
+			"S" => Classifier::Pork,
+			"SAT" => Classifier::OrganicPork,
+			"R" => Classifier::Beef,
+			"RAT" => Classifier::OrganicBeef,
+			"GEL" => Classifier::Gelatine,
+			"MSC" => Classifier::Fish,
+			"LAB" => Classifier::AnimalRennet,
+			"VEG" => Classifier::Vegetarian,
+			"VG" => Classifier::Vegan,
+			"MV" => Classifier::MensaVital,
+			_ => return None,
+		})
+	}
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct NaiveDateWrapper {
@@ -109,16 +154,59 @@ struct Date {
 	pub year: i64,
 }
 
+/// A cached value together with the point in time it was fetched, so callers
+/// can decide whether it is still fresh enough to reuse.
+#[derive(Debug, Clone, Default)]
+enum Fetchable<T> {
+	#[default]
+	None,
+	Fetched(T, Instant),
+}
+
+impl<T: Clone> Fetchable<T> {
+	/// Returns the cached value if it is younger than `local_ttl`, otherwise
+	/// runs `f` to obtain a fresh value and caches it.
+	async fn fetch<F, Fut>(&mut self, local_ttl: Duration, f: F) -> Result<T>
+	where
+		F: FnOnce() -> Fut,
+		Fut: std::future::Future<Output = Result<T>>,
+	{
+		if let Fetchable::Fetched(value, fetched_at) = self {
+			if fetched_at.elapsed() < local_ttl {
+				return Ok(value.clone());
+			}
+		}
+
+		let value = f().await?;
+		*self = Fetchable::Fetched(value.clone(), Instant::now());
+		Ok(value)
+	}
+}
+
 pub struct MensaApi {
 	base_url: Url,
+	cache_ttl: Duration,
+	plans: RwLock<Fetchable<Vec<NaiveDate>>>,
+	canteen_data: RwLock<HashMap<NaiveDate, Fetchable<Vec<CanteenData>>>>,
 }
 
 impl MensaApi {
-	pub fn new(base_url: Url) -> Self {
+	pub fn new(base_url: Url, cache_ttl: Duration) -> Self {
 		Self {
 			base_url,
+			cache_ttl,
+			plans: RwLock::new(Fetchable::None),
+			canteen_data: RwLock::new(HashMap::new()),
 		}
 	}
+
+	/// Drops all cached menu data, forcing the next lookup to hit the
+	/// upstream API again. Useful right before an announcement edit, so it
+	/// always reflects the latest menu rather than a stale local copy.
+	pub async fn clear_cache(&self) {
+		*self.plans.write().await = Fetchable::None;
+		self.canteen_data.write().await.clear();
+	}
 }
 
 // implement deserialize for NaiveDate via deserialize_with using Date struct as a single function
@@ -132,26 +220,46 @@ where D: Deserializer<'de> {
 
 impl MensaApi {
 	pub async fn get_available_plans(&self) -> Result<Vec<NaiveDate>> {
-		let url = self.base_url.join("plans").into_diagnostic().wrap_err("Failed to construct url for available plans.")?;
-		let response = reqwest::get(url).await.into_diagnostic().wrap_err("Failed to fetch available plans.")?;
+		let base_url = &self.base_url;
+		let mut plans = self.plans.write().await;
+		plans
+			.fetch(self.cache_ttl, || async {
+				let url = base_url.join("plans").into_diagnostic().wrap_err("Failed to construct url for available plans.")?;
+				let response = reqwest::get(url).await.into_diagnostic().wrap_err("Failed to fetch available plans.")?;
 
-		let data =
-			response.json::<ApiResult<Vec<NaiveDateWrapper>>>().await.into_diagnostic().wrap_err("Failed to parse available plans.")?;
+				let data = response
+					.json::<ApiResult<Vec<NaiveDateWrapper>>>()
+					.await
+					.into_diagnostic()
+					.wrap_err("Failed to parse available plans.")?;
 
-		let plans = data.data.into_iter().map(|plan| plan.date).collect();
-		Ok(plans)
+				Ok(data.data.into_iter().map(|plan| plan.date).collect())
+			})
+			.await
 	}
 
 	pub async fn get_canteen_data(&self, day: &NaiveDate) -> Result<Vec<CanteenData>> {
-		// date needs to be in format YYYY-MM-DD
-		let day = day.format("%Y-%m-%d").to_string();
-		let url =
-			self.base_url.join(&format!("plans/{}", day)).into_diagnostic().wrap_err("Failed to construct url for canteen data.")?;
-		let response = reqwest::get(url).await.into_diagnostic().wrap_err("Failed to fetch canteen data.")?;
+		let base_url = &self.base_url;
+		let mut cache = self.canteen_data.write().await;
+		let cached = cache.entry(*day).or_default();
+		cached
+			.fetch(self.cache_ttl, || async {
+				// date needs to be in format YYYY-MM-DD
+				let formatted_day = day.format("%Y-%m-%d").to_string();
+				let url = base_url
+					.join(&format!("plans/{}", formatted_day))
+					.into_diagnostic()
+					.wrap_err("Failed to construct url for canteen data.")?;
+				let response = reqwest::get(url).await.into_diagnostic().wrap_err("Failed to fetch canteen data.")?;
 
-		let data =
-			response.json::<ApiResult<Vec<CanteenData>>>().await.into_diagnostic().wrap_err("Failed to parse canteen data.")?;
+				let data = response
+					.json::<ApiResult<Vec<CanteenData>>>()
+					.await
+					.into_diagnostic()
+					.wrap_err("Failed to parse canteen data.")?;
 
-		Ok(data.data)
+				Ok(data.data)
+			})
+			.await
 	}
 }
@@ -0,0 +1,127 @@
+use std::{
+	path::Path,
+	sync::{
+		Arc,
+		Mutex,
+	},
+};
+
+use miette::{
+	IntoDiagnostic,
+	Result,
+	WrapErr,
+};
+use rusqlite::{
+	params,
+	Connection,
+	OptionalExtension,
+};
+use serenity::prelude::TypeMapKey;
+
+use crate::mensa::api::Classifier;
+
+pub struct PrefsStoreKey;
+impl TypeMapKey for PrefsStoreKey {
+	type Value = PrefsStore;
+}
+
+/// The preferences a user has set via `/mensa-prefs`, one row per Discord
+/// user id.
+#[derive(Debug, Default, Clone)]
+pub struct UserPrefs {
+	pub canteen_id: Option<String>,
+	pub dietary_filter: Option<Classifier>,
+}
+
+/// `rusqlite::Connection` is blocking, so every query is run on the blocking
+/// thread pool via [`tokio::task::spawn_blocking`] instead of inline in an
+/// async fn, where it would otherwise stall a tokio worker thread.
+pub struct PrefsStore {
+	conn: Arc<Mutex<Connection>>,
+}
+
+impl PrefsStore {
+	pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+		let conn = Connection::open(path).into_diagnostic().wrap_err("Failed to open preferences database.")?;
+		conn.execute(
+			"CREATE TABLE IF NOT EXISTS user_prefs (
+				user_id INTEGER PRIMARY KEY,
+				canteen_id TEXT,
+				dietary_filter TEXT
+			)",
+			[],
+		)
+		.into_diagnostic()
+		.wrap_err("Failed to run preferences migration.")?;
+
+		Ok(Self {
+			conn: Arc::new(Mutex::new(conn)),
+		})
+	}
+
+	pub async fn get(&self, user_id: u64) -> Result<UserPrefs> {
+		let conn = self.conn.clone();
+		tokio::task::spawn_blocking(move || {
+			let conn = conn.lock().unwrap();
+			let row = conn
+				.query_row("SELECT canteen_id, dietary_filter FROM user_prefs WHERE user_id = ?1", params![user_id as i64], |row| {
+					let canteen_id: Option<String> = row.get(0)?;
+					let dietary_filter: Option<String> = row.get(1)?;
+					Ok((canteen_id, dietary_filter))
+				})
+				.optional()
+				.into_diagnostic()
+				.wrap_err("Failed to load user preferences.")?;
+
+			let (canteen_id, dietary_filter) = row.unwrap_or_default();
+			Ok(UserPrefs {
+				canteen_id,
+				dietary_filter: dietary_filter.and_then(|code| Classifier::from_code(&code)),
+			})
+		})
+		.await
+		.into_diagnostic()
+		.wrap_err("Preferences worker task panicked.")?
+	}
+
+	pub async fn set_canteen(&self, user_id: u64, canteen_id: Option<&str>) -> Result<()> {
+		let conn = self.conn.clone();
+		let canteen_id = canteen_id.map(str::to_string);
+		tokio::task::spawn_blocking(move || {
+			conn.lock()
+				.unwrap()
+				.execute(
+					"INSERT INTO user_prefs (user_id, canteen_id) VALUES (?1, ?2)
+					 ON CONFLICT(user_id) DO UPDATE SET canteen_id = excluded.canteen_id",
+					params![user_id as i64, canteen_id],
+				)
+				.into_diagnostic()
+				.wrap_err("Failed to store canteen preference.")?;
+
+			Ok(())
+		})
+		.await
+		.into_diagnostic()
+		.wrap_err("Preferences worker task panicked.")?
+	}
+
+	pub async fn set_dietary_filter(&self, user_id: u64, filter: Option<Classifier>) -> Result<()> {
+		let conn = self.conn.clone();
+		tokio::task::spawn_blocking(move || {
+			conn.lock()
+				.unwrap()
+				.execute(
+					"INSERT INTO user_prefs (user_id, dietary_filter) VALUES (?1, ?2)
+					 ON CONFLICT(user_id) DO UPDATE SET dietary_filter = excluded.dietary_filter",
+					params![user_id as i64, filter.map(Classifier::code)],
+				)
+				.into_diagnostic()
+				.wrap_err("Failed to store dietary filter preference.")?;
+
+			Ok(())
+		})
+		.await
+		.into_diagnostic()
+		.wrap_err("Preferences worker task panicked.")?
+	}
+}